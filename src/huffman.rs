@@ -2,22 +2,100 @@
 
 use std::{
     cell::RefCell,
-    collections::{hash_map::Iter, HashMap},
+    cmp::{Ordering, Reverse},
+    collections::{hash_map::Iter, BinaryHeap, HashMap},
+    hash::Hash,
     ops::AddAssign,
     rc::Rc,
 };
 
 type Weight = u64;
 
-/// 字符权重
-pub struct CharWeightMap {
-    pub inner: HashMap<char, Weight>,
+/// 比特序列
+///
+/// 用于承载霍夫曼编码后的二进制数据
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BitVec {
+    bits: Vec<bool>,
 }
 
-/// 计算字符权重
+impl BitVec {
+    pub fn new() -> Self {
+        Self { bits: Vec::new() }
+    }
+
+    fn from_bits(bits: Vec<bool>) -> Self {
+        Self { bits }
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        self.bits.push(bit);
+    }
+
+    /// 将另一个比特序列追加到末尾
+    pub fn extend(&mut self, other: &BitVec) {
+        self.bits.extend_from_slice(&other.bits);
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.bits.get(index).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.bits.iter().copied()
+    }
+
+    /// 打包成字节序列，不足一个字节的部分用 `0` 补齐
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; self.bits.len().div_ceil(8)];
+        for (index, bit) in self.bits.iter().enumerate() {
+            if *bit {
+                bytes[index / 8] |= 1 << (7 - index % 8);
+            }
+        }
+        bytes
+    }
+
+    /// 从字节序列中还原出前 `bit_len` 个比特
+    pub fn from_bytes(bytes: &[u8], bit_len: usize) -> Self {
+        let bits = (0..bit_len)
+            .map(|index| bytes[index / 8] & (1 << (7 - index % 8)) != 0)
+            .collect();
+        Self { bits }
+    }
+}
+
+/// 符号权重
 ///
-/// 计算每个字符出现的次数作为权重
-impl CharWeightMap {
+/// 统计每个符号出现的次数作为权重，符号类型 `T` 既可以是 `char` 也可以是 `u8` 等其它类型
+pub struct WeightMap<T> {
+    pub inner: HashMap<T, Weight>,
+}
+
+impl<T: Eq + Hash + Clone> WeightMap<T> {
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, Weight> {
+        self.inner.iter()
+    }
+}
+
+/// 计算字符权重
+impl WeightMap<char> {
     pub fn build(input: &str) -> Self {
         let mut map = HashMap::new();
         for (_, char) in input.char_indices() {
@@ -25,95 +103,411 @@ impl CharWeightMap {
         }
         Self { inner: map }
     }
+}
 
-    pub fn len(&self) -> usize {
-        self.inner.len()
+/// 计算字节权重，用于对任意二进制数据做霍夫曼编码
+impl WeightMap<u8> {
+    pub fn build_from_bytes(bytes: &[u8]) -> Self {
+        let mut map = HashMap::new();
+        for byte in bytes {
+            map.entry(*byte).or_insert(0).add_assign(1);
+        }
+        Self { inner: map }
     }
+}
 
-    pub fn iter(&self) -> Iter<char, Weight> {
-        self.inner.iter()
+/// 字符权重表，历史上的默认用法，保留原名字作为别名
+pub type CharWeightMap = WeightMap<char>;
+
+type RefHuffmanNode<T> = Rc<RefCell<HuffmanNode<T>>>;
+
+/// 堆中的一个待合并节点，按 (权重, 高度) 排序，保证相同权重集合下合并顺序是确定的
+struct HeapEntry<T> {
+    weight: Weight,
+    height: u64,
+    node: RefHuffmanNode<T>,
+}
+
+impl<T> HeapEntry<T> {
+    fn new(node: RefHuffmanNode<T>) -> Self {
+        let (weight, height) = {
+            let node = node.borrow();
+            (node.weight, node.height)
+        };
+        Self {
+            weight,
+            height,
+            node,
+        }
+    }
+}
+
+impl<T> PartialEq for HeapEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.weight == other.weight && self.height == other.height
     }
 }
 
-type RefHuffmanTree = Rc<RefCell<HuffmanTree>>;
+impl<T> Eq for HeapEntry<T> {}
+
+impl<T> PartialOrd for HeapEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for HeapEntry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 权重相同时高度更矮的节点优先弹出，与 left/right 的归并规则保持一致
+        self.weight
+            .cmp(&other.weight)
+            .then_with(|| self.height.cmp(&other.height))
+    }
+}
 
 /// 霍夫曼树
-pub struct HuffmanTree {
+pub struct HuffmanNode<T> {
     /// 值
-    pub value: Option<char>,
+    pub value: Option<T>,
     /// 权重
     pub weight: Weight,
+    /// 高度，叶子为 1，父节点为子节点高度较大者加一，用于归并时的 tie-break
+    pub height: u64,
     /// 父节点
-    pub parent: Option<RefHuffmanTree>,
+    pub parent: Option<RefHuffmanNode<T>>,
     /// 左分支
-    pub left: Option<RefHuffmanTree>,
+    pub left: Option<RefHuffmanNode<T>>,
     /// 右分支
-    pub right: Option<RefHuffmanTree>,
+    pub right: Option<RefHuffmanNode<T>>,
 }
 
-impl HuffmanTree {
+/// 霍夫曼树，历史上的默认用法，保留原名字作为别名
+pub type HuffmanTree = HuffmanNode<char>;
+
+impl<T: Eq + Hash + Clone + Ord> HuffmanNode<T> {
     pub fn new() -> Self {
         Self {
             value: None,
             weight: 0,
+            height: 1,
             parent: None,
             left: None,
             right: None,
         }
     }
-    pub fn build(char_weight: &CharWeightMap) -> RefHuffmanTree {
-        // 原始节点数量
-        let n = char_weight.len();
-        // 构建完整霍夫曼树总共需要的节点数量
-        let total = 2 * n - 1;
-        // 初始化所有节点
-        let vec = (0..total)
-            .map(|_| Rc::new(RefCell::new(Self::new())))
-            .collect::<Vec<Rc<RefCell<HuffmanTree>>>>();
-
-        // 字符节点赋值
-        char_weight
-            .iter()
-            .enumerate()
+
+    /// 构建霍夫曼树，字母表为空时返回 `None`（此时没有任何符号可编码）
+    ///
+    /// 注：这里的签名是 `Option<RefHuffmanNode<T>>` 而不是恒返回一棵树——
+    /// 字母表为空时堆里没有任何叶子，不存在"空树"这种霍夫曼树可以表示，
+    /// 勉强构造一个哨兵节点只是把问题推给调用方自己判断；让 `build` 如实
+    /// 返回 `None`，交给 `compress`/`decompress` 这些调用方显式处理，
+    /// 比假装总能返回一棵树更不容易出错
+    pub fn build(weight_map: &WeightMap<T>) -> Option<RefHuffmanNode<T>> {
+        // 先按 (权重, 符号) 排序再入堆：HashMap 的遍历顺序本身不保证稳定，
+        // 如果权重相同的叶子入堆顺序不固定，解压时从同一份频率表重建出的树
+        // 就可能和压缩时的树形状一致但符号分配不同，导致解码出错
+        let mut leaves: Vec<(&T, &Weight)> = weight_map.iter().collect();
+        leaves.sort_by(|(s1, w1), (s2, w2)| w1.cmp(w2).then_with(|| s1.cmp(s2)));
+
+        // 把所有叶子节点丢进小顶堆，每次弹出两个最小的合并成一个新节点再放回去
+        let mut heap: BinaryHeap<Reverse<HeapEntry<T>>> = leaves
             .into_iter()
-            .for_each(|(index, (ch, weight))| {
-                vec[index].borrow_mut().value = Some(*ch);
-                vec[index].borrow_mut().weight = *weight;
-            });
-
-        for index in n..total {
-            // 找到 [0, index-1] 中权重最小的节点
-            let m1 = Self::find_min(&vec[..index]).unwrap();
-            // 标记父节点为 index 上的节点，下次就不会找到这个
-            m1.borrow_mut().parent = Some(vec[index].clone());
-            // 找到 [0, index-1] 中权重第二小的节点
-            let m2 = Self::find_min(&vec[..index]).unwrap();
-            // 标记该节点的父节点为 index 上的节点
-            m2.borrow_mut().parent = Some(vec[index].clone());
-
-            let w1 = m1.as_ref().borrow().weight;
-            let w2 = m2.as_ref().borrow().weight;
-            let weight = w1 + w2;
-
-            vec[index].borrow_mut().weight = weight;
-            vec[index].borrow_mut().left = Some(m1.clone());
-            vec[index].borrow_mut().right = Some(m2.clone());
-        }
-        // 最后一个节点即是构建好的完整霍夫曼树
-        vec.last().unwrap().clone()
-    }
-
-    /// 获取最小的值
-    fn find_min(tree_slice: &[Rc<RefCell<HuffmanTree>>]) -> Option<Rc<RefCell<HuffmanTree>>> {
-        let mut min = Weight::MAX;
-        let mut result = None;
-        for tree in tree_slice {
-            let tree_cell = tree.as_ref();
-            if tree_cell.borrow().parent.is_none() && tree_cell.borrow().weight < min {
-                min = tree_cell.borrow().weight;
-                result = Some(tree.clone());
+            .map(|(symbol, weight)| {
+                let mut leaf = Self::new();
+                leaf.value = Some(symbol.clone());
+                leaf.weight = *weight;
+                HeapEntry::new(Rc::new(RefCell::new(leaf)))
+            })
+            .map(Reverse)
+            .collect();
+
+        while heap.len() > 1 {
+            // 堆按 (权重, 高度) 升序弹出，m1 始终该作为 left，m2 作为 right
+            let Reverse(m1) = heap.pop().unwrap();
+            let Reverse(m2) = heap.pop().unwrap();
+
+            let parent = Rc::new(RefCell::new(Self::new()));
+            parent.borrow_mut().weight = m1.weight + m2.weight;
+            parent.borrow_mut().height = m1.height.max(m2.height) + 1;
+            m1.node.borrow_mut().parent = Some(parent.clone());
+            m2.node.borrow_mut().parent = Some(parent.clone());
+            parent.borrow_mut().left = Some(m1.node);
+            parent.borrow_mut().right = Some(m2.node);
+
+            heap.push(Reverse(HeapEntry::new(parent)));
+        }
+        // 堆中剩下的最后一个节点即是构建好的完整霍夫曼树
+        heap.pop().map(|Reverse(entry)| entry.node)
+    }
+
+    /// 编码
+    ///
+    /// 将符号序列中的每个符号替换为其对应的霍夫曼编码并拼接成一个比特序列
+    pub fn encode_symbols(&self, symbols: &[T]) -> BitVec {
+        let codes = self.build_code_table();
+        let mut bits = BitVec::new();
+        for symbol in symbols {
+            if let Some(code) = codes.get(symbol) {
+                bits.extend(code);
+            }
+        }
+        bits
+    }
+
+    /// 解码
+    ///
+    /// 从根节点出发，按比特序列左右下行，每到达一个叶子节点就输出其值并回到根节点重新出发
+    pub fn decode_symbols(&self, bits: &BitVec) -> Vec<T> {
+        self.decode_symbols_bounded(bits, bits.len())
+    }
+
+    /// 解码出前 `symbol_count` 个符号就停止
+    ///
+    /// 压缩容器按字节对齐存储比特流，末尾可能混入补齐用的填充位，
+    /// 所以解压时要依据存储下来的原始符号数量来判断何时停止，而不是简单地耗尽比特流
+    fn decode_symbols_bounded(&self, bits: &BitVec, symbol_count: usize) -> Vec<T> {
+        let mut result = Vec::new();
+
+        // 只有一个符号时没有分支，固定占用一个比特
+        if self.left.is_none() && self.right.is_none() {
+            if let Some(symbol) = &self.value {
+                result.extend(std::iter::repeat_n(symbol.clone(), symbol_count));
+            }
+            return result;
+        }
+
+        let mut current: Option<RefHuffmanNode<T>> = None;
+        for bit in bits.iter() {
+            if result.len() == symbol_count {
+                break;
+            }
+            let (left, right) = match &current {
+                Some(node) => {
+                    let node = node.borrow();
+                    (node.left.clone(), node.right.clone())
+                }
+                None => (self.left.clone(), self.right.clone()),
+            };
+            current = if bit { right } else { left };
+
+            if let Some(node) = &current {
+                let symbol = node.borrow().value.clone();
+                if let Some(symbol) = symbol {
+                    result.push(symbol);
+                    current = None;
+                }
             }
         }
         result
     }
+
+    /// 构建每个符号对应的编码表
+    ///
+    /// 从每个叶子节点沿 parent 指针走到根，途中是父节点的 left 记 `0`、right 记 `1`，
+    /// 收集后反转即得到该符号从根到叶的编码
+    fn build_code_table(&self) -> HashMap<T, BitVec> {
+        let mut codes = HashMap::new();
+
+        if self.left.is_none() && self.right.is_none() {
+            if let Some(symbol) = &self.value {
+                codes.insert(symbol.clone(), BitVec::from_bits(vec![false]));
+            }
+            return codes;
+        }
+
+        Self::collect_codes(&self.left, &mut codes);
+        Self::collect_codes(&self.right, &mut codes);
+        codes
+    }
+
+    fn collect_codes(node: &Option<RefHuffmanNode<T>>, codes: &mut HashMap<T, BitVec>) {
+        let Some(node) = node else { return };
+        let node_ref = node.borrow();
+        if let Some(symbol) = &node_ref.value {
+            codes.insert(symbol.clone(), Self::code_for(node));
+        } else {
+            Self::collect_codes(&node_ref.left, codes);
+            Self::collect_codes(&node_ref.right, codes);
+        }
+    }
+
+    /// 从叶子节点沿 parent 指针回溯到根，得到这个叶子的编码
+    fn code_for(leaf: &RefHuffmanNode<T>) -> BitVec {
+        let mut bits = Vec::new();
+        let mut current = leaf.clone();
+        loop {
+            let parent = current.borrow().parent.clone();
+            match parent {
+                Some(parent) => {
+                    let is_right = parent
+                        .borrow()
+                        .right
+                        .as_ref()
+                        .is_some_and(|right| Rc::ptr_eq(right, &current));
+                    bits.push(is_right);
+                    current = parent;
+                }
+                None => break,
+            }
+        }
+        bits.reverse();
+        BitVec::from_bits(bits)
+    }
+
+    /// 中序遍历，依次产出 左子树 - 根 - 右子树 的权重
+    ///
+    /// 对于同一组权重，规范化构建的树是唯一的，可以用这个序列来校验
+    pub fn in_order(&self) -> Vec<Weight> {
+        let mut weights = Vec::new();
+        if let Some(left) = &self.left {
+            weights.extend(left.borrow().in_order());
+        }
+        weights.push(self.weight);
+        if let Some(right) = &self.right {
+            weights.extend(right.borrow().in_order());
+        }
+        weights
+    }
+}
+
+impl<T: Eq + Hash + Clone + Ord> Default for HuffmanNode<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 原本基于 `char` 的编解码接口，保留下来维持既有调用方式不变
+impl HuffmanTree {
+    /// 将输入字符串中的每个字符替换为其对应的霍夫曼编码并拼接成一个比特序列
+    pub fn encode(&self, input: &str) -> BitVec {
+        let symbols: Vec<char> = input.chars().collect();
+        self.encode_symbols(&symbols)
+    }
+
+    /// 从比特序列还原出原始字符串
+    pub fn decode(&self, bits: &BitVec) -> String {
+        self.decode_symbols(bits).into_iter().collect()
+    }
+
+    fn decode_bounded(&self, bits: &BitVec, symbol_count: usize) -> String {
+        self.decode_symbols_bounded(bits, symbol_count)
+            .into_iter()
+            .collect()
+    }
+
+    /// 每个字符对应的前缀编码表，以 `'0'`/`'1'` 组成的字符串表示
+    pub fn codes(&self) -> HashMap<char, String> {
+        self.codes_and_wpl().0
+    }
+
+    /// 带权路径长度（WPL）：所有叶子的 权重 * 深度 之和，也就是编码后占用的总比特数
+    ///
+    /// 单符号字母表中唯一的叶子就是根节点，它的编码固定占一个比特，因此按深度 1 计算
+    ///
+    /// 这是霍夫曼树要最小化的目标，可以用来和定长编码比较压缩率
+    pub fn weighted_path_length(&self) -> u64 {
+        self.codes_and_wpl().1
+    }
+
+    /// 从根节点出发做一次深度遍历，同时算出编码表和带权路径长度，避免重复遍历整棵树
+    fn codes_and_wpl(&self) -> (HashMap<char, String>, u64) {
+        let mut codes = HashMap::new();
+        let mut wpl = 0;
+        self.walk_codes(0, &mut String::new(), &mut codes, &mut wpl);
+        (codes, wpl)
+    }
+
+    fn walk_codes(
+        &self,
+        depth: u64,
+        prefix: &mut String,
+        codes: &mut HashMap<char, String>,
+        wpl: &mut u64,
+    ) {
+        if self.left.is_none() && self.right.is_none() {
+            if let Some(ch) = self.value {
+                // 单符号字母表没有分支，固定占用一个比特
+                let code = if prefix.is_empty() {
+                    "0".to_string()
+                } else {
+                    prefix.clone()
+                };
+                *wpl += self.weight * depth.max(1);
+                codes.insert(ch, code);
+            }
+            return;
+        }
+
+        if let Some(left) = &self.left {
+            prefix.push('0');
+            left.borrow().walk_codes(depth + 1, prefix, codes, wpl);
+            prefix.pop();
+        }
+        if let Some(right) = &self.right {
+            prefix.push('1');
+            right.borrow().walk_codes(depth + 1, prefix, codes, wpl);
+            prefix.pop();
+        }
+    }
+}
+
+/// 压缩
+///
+/// 容器格式依次为：频率表（字符数 + 每个字符及其权重）、原始符号总数、位压缩后的编码数据。
+/// 解压时从频率表重建出与压缩时完全一致的规范霍夫曼树，因此不需要额外保存编码表。
+/// 空字符串没有任何符号可编码，此时频率表为空、编码数据也为空
+pub fn compress(input: &str) -> Vec<u8> {
+    let char_weight = CharWeightMap::build(input);
+
+    let mut bytes = Vec::new();
+    bytes.extend((char_weight.len() as u32).to_le_bytes());
+    for (ch, weight) in char_weight.iter() {
+        bytes.extend((*ch as u32).to_le_bytes());
+        bytes.extend(weight.to_le_bytes());
+    }
+    bytes.extend((input.chars().count() as u64).to_le_bytes());
+
+    if let Some(tree) = HuffmanTree::build(&char_weight) {
+        let bits = tree.borrow().encode(input);
+        bytes.extend(bits.to_bytes());
+    }
+    bytes
+}
+
+/// 解压
+///
+/// 假定 `bytes` 是 `compress` 产出的、未被截断或篡改的数据；头部不完整或字符编号非法时会 panic，
+/// 只有编码数据被截断或篡改时不会 panic，而是返回更短或错误的字符串，不是用来处理不可信输入的
+pub fn decompress(bytes: &[u8]) -> String {
+    let mut cursor = 0usize;
+
+    let char_count = u32::from_le_bytes(bytes[cursor..cursor + 4].try_into().unwrap()) as usize;
+    cursor += 4;
+
+    let mut inner = HashMap::with_capacity(char_count);
+    for _ in 0..char_count {
+        let ch = u32::from_le_bytes(bytes[cursor..cursor + 4].try_into().unwrap());
+        let ch = char::from_u32(ch).expect("压缩数据中的字符编号非法");
+        cursor += 4;
+
+        let weight = Weight::from_le_bytes(bytes[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+
+        inner.insert(ch, weight);
+    }
+    let char_weight = CharWeightMap { inner };
+
+    let symbol_count = u64::from_le_bytes(bytes[cursor..cursor + 8].try_into().unwrap()) as usize;
+    cursor += 8;
+
+    // 空字母表（压缩时输入为空字符串）没有树可建，原始内容也必然是空串
+    let Some(tree) = HuffmanTree::build(&char_weight) else {
+        return String::new();
+    };
+
+    let payload = &bytes[cursor..];
+    let bits = BitVec::from_bytes(payload, payload.len() * 8);
+    let node = tree.borrow();
+    node.decode_bounded(&bits, symbol_count)
 }